@@ -1,11 +1,22 @@
+mod matrix;
+mod quantile;
+
+pub use matrix::Matrix;
+use quantile::Quantile;
 use std::time::Duration;
 
 pub const ITERATIONS: usize = 9;
 
+const DEFAULT_EPSILON: f64 = 0.01;
+
 pub struct Benchmark {
     name: &'static str,
     results: Vec<u32>,
-    durations: Vec<Duration>,
+    count: u32,
+    sum: Duration,
+    min: Duration,
+    max: Duration,
+    quantile: Quantile,
 }
 
 impl Benchmark {
@@ -13,28 +24,70 @@ impl Benchmark {
         Benchmark {
             name,
             results: Vec::with_capacity(ITERATIONS),
-            durations: Vec::with_capacity(ITERATIONS),
+            count: 0,
+            sum: Duration::default(),
+            min: Duration::MAX,
+            max: Duration::default(),
+            quantile: Quantile::new(DEFAULT_EPSILON),
         }
     }
 
     pub fn add(&mut self, result: u32, duration: Duration) {
         self.results.push(result);
-        self.durations.push(duration);
+        self.count += 1;
+        self.sum += duration;
+        self.min = self.min.min(duration);
+        self.max = self.max.max(duration);
+        self.quantile.update(duration.as_nanos() as u64);
+    }
+
+    pub fn report_quantiles(&self, phis: &[f64]) {
+        for &phi in phis {
+            let nanos = self.quantile.query(phi);
+            println!("p{:.0} = {:?}", phi * 100.0, Duration::from_nanos(nanos));
+        }
     }
 
     pub fn print(&mut self) {
         assert!(self.results.iter().all(|&r| r == self.results[0]));
-        self.durations.sort();
-        let len = self.durations.len();
-        let avg = self.durations.iter().sum::<Duration>() / len as u32;
-        let mid = self.durations.get(len / 2).unwrap();
-        let min = self.durations.first().unwrap();
-        let max = self.durations.last().unwrap();
+        let avg = self.sum / self.count;
         println!("[{}]", self.name);
         println!("avg = {:?}", avg);
-        println!("mid = {:?}", mid);
-        println!("min = {:?}", min);
-        println!("max = {:?}", max);
+        self.report_quantiles(&[0.5, 0.9, 0.99]);
+        println!("min = {:?}", self.min);
+        println!("max = {:?}", self.max);
+        println!();
+    }
+
+    pub fn throughput(&self, n_elements: u64) -> f64 {
+        let avg = self.sum / self.count;
+        n_elements as f64 / avg.as_secs_f64()
+    }
+}
+
+pub struct BenchmarkSweep {
+    name: &'static str,
+    points: Vec<(u64, f64)>,
+}
+
+impl BenchmarkSweep {
+    pub fn new(name: &'static str) -> BenchmarkSweep {
+        BenchmarkSweep {
+            name,
+            points: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, n_elements: u64, throughput: f64) {
+        self.points.push((n_elements, throughput));
+    }
+
+    pub fn print(&self) {
+        println!("# {}", self.name);
+        for (n_elements, throughput) in &self.points {
+            println!("{} {}", n_elements, throughput);
+        }
+        println!("e");
         println!();
     }
 }
@@ -1,138 +1,403 @@
-use benchmark::{Benchmark, ITERATIONS};
+use benchmark::{Benchmark, BenchmarkSweep, Matrix, ITERATIONS};
 use scoped_threadpool::Pool;
-use std::time::Instant;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 
 const DIM: usize = 10_000;
 const P: usize = 4;
+const BLOCK: usize = 64;
+
+const SWEEP_MIN_LOG2_N: u32 = 10;
+const SWEEP_MAX_LOG2_N: u32 = 26;
+
+const BLOCK_SWEEP_MIN_LOG2: u32 = 3;
+const BLOCK_SWEEP_MAX_LOG2: u32 = 9;
+
+const FLOOD_DIM: usize = 2_000;
+const FLOOD_LABELS: u8 = 4;
+
+const NEIGHBORS: [(isize, isize); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
 
 fn main() {
-    let matrix = build_random_u8_square_matrix_nested();
+    let matrix = build_random_u8_matrix(DIM);
     count_odds_row_major_traversal(&matrix);
     count_odds_col_major_traversal(&matrix);
-
-    let matrix = build_random_u8_square_matrix_inline();
+    count_odds_blocked_traversal(&matrix);
+    count_odds_blocked_transpose_traversal(&matrix);
     count_odds_sequential(&matrix);
     count_odds_parallel(&matrix);
     count_odds_parallel_optimized(&matrix);
+
+    run_sweep();
+    run_block_sweep();
+    run_thread_sweep();
+    run_flood_fill_demo();
 }
 
-fn build_random_u8_square_matrix_nested() -> Vec<Vec<u8>> {
-    (0..DIM)
-        .map(|_| (0..DIM).map(|_| rand::random()).collect())
-        .collect()
+fn build_random_u8_matrix(dim: usize) -> Matrix<u8> {
+    Matrix::new((0..dim * dim).map(|_| rand::random()).collect(), dim)
 }
 
-fn build_random_u8_square_matrix_inline() -> Vec<u8> {
-    (0..DIM * DIM).map(|_| rand::random()).collect()
+fn time_it<F: FnMut() -> u32>(mut f: F) -> (u32, Duration) {
+    let t0 = Instant::now();
+    let odds = f();
+    (odds, t0.elapsed())
 }
 
-#[allow(clippy::needless_range_loop)]
-fn count_odds_row_major_traversal(matrix: &[Vec<u8>]) {
-    let mut benchmark = Benchmark::new("count_odds_row_major_traversal");
-    for _ in 0..ITERATIONS {
-        let t0 = Instant::now();
-        let mut odds = 0;
-        for r in 0..DIM {
-            for c in 0..DIM {
-                if matrix[r][c] % 2 != 0 {
-                    odds += 1;
+fn count_odds_row_major(matrix: &Matrix<u8>, dim: usize) -> u32 {
+    let mut odds = 0;
+    for r in 0..dim {
+        for c in 0..dim {
+            if !matrix[r][c].is_multiple_of(2) {
+                odds += 1;
+            }
+        }
+    }
+    odds
+}
+
+fn count_odds_col_major(matrix: &Matrix<u8>, dim: usize) -> u32 {
+    let mut odds = 0;
+    for c in 0..dim {
+        for r in 0..dim {
+            if !matrix[r][c].is_multiple_of(2) {
+                odds += 1;
+            }
+        }
+    }
+    odds
+}
+
+fn count_odds_blocked(matrix: &Matrix<u8>, dim: usize, block: usize) -> u32 {
+    let mut odds = 0;
+    for bi in (0..dim).step_by(block) {
+        for bj in (0..dim).step_by(block) {
+            for i in bi..std::cmp::min(bi + block, dim) {
+                for j in bj..std::cmp::min(bj + block, dim) {
+                    if !matrix[i][j].is_multiple_of(2) {
+                        odds += 1;
+                    }
                 }
             }
         }
-        benchmark.add(odds, t0.elapsed());
     }
-    benchmark.print();
+    odds
 }
 
-#[allow(clippy::needless_range_loop)]
-fn count_odds_col_major_traversal(matrix: &[Vec<u8>]) {
-    let mut benchmark = Benchmark::new("count_odds_col_major_traversal");
-    for _ in 0..ITERATIONS {
-        let t0 = Instant::now();
-        let mut odds = 0;
-        for c in 0..DIM {
-            for r in 0..DIM {
-                if matrix[r][c] % 2 != 0 {
-                    odds += 1;
+fn count_odds_blocked_transpose(matrix: &Matrix<u8>, dim: usize, block: usize) -> u32 {
+    let mut odds = 0;
+    for bj in (0..dim).step_by(block) {
+        for bi in (0..dim).step_by(block) {
+            for j in bj..std::cmp::min(bj + block, dim) {
+                for i in bi..std::cmp::min(bi + block, dim) {
+                    if !matrix[i][j].is_multiple_of(2) {
+                        odds += 1;
+                    }
                 }
             }
         }
-        benchmark.add(odds, t0.elapsed());
+    }
+    odds
+}
+
+fn count_odds_sequential_impl(matrix: &Matrix<u8>, dim: usize) -> u32 {
+    let mut odds = 0;
+    for i in 0..dim {
+        for j in 0..dim {
+            if !matrix[i][j].is_multiple_of(2) {
+                odds += 1;
+            }
+        }
+    }
+    odds
+}
+
+fn count_odds_parallel_impl(matrix: &Matrix<u8>, dim: usize) -> u32 {
+    let mut pool = Pool::new(P as u32);
+    let mut results = [0; P];
+    pool.scoped(|scope| {
+        for (p, results_p) in results.iter_mut().enumerate() {
+            scope.execute(move || {
+                let chunk_size = dim / P + 1;
+                let my_start = p * chunk_size;
+                let my_end = std::cmp::min(my_start + chunk_size, dim);
+                for i in my_start..my_end {
+                    for j in 0..dim {
+                        if !matrix[i][j].is_multiple_of(2) {
+                            *results_p += 1;
+                        }
+                    }
+                }
+            });
+        }
+    });
+    results.iter().sum()
+}
+
+fn count_odds_row_major_traversal(matrix: &Matrix<u8>) {
+    let mut benchmark = Benchmark::new("count_odds_row_major_traversal");
+    for _ in 0..ITERATIONS {
+        let (odds, duration) = time_it(|| count_odds_row_major(matrix, DIM));
+        benchmark.add(odds, duration);
+    }
+    benchmark.print();
+}
+
+fn count_odds_col_major_traversal(matrix: &Matrix<u8>) {
+    let mut benchmark = Benchmark::new("count_odds_col_major_traversal");
+    for _ in 0..ITERATIONS {
+        let (odds, duration) = time_it(|| count_odds_col_major(matrix, DIM));
+        benchmark.add(odds, duration);
+    }
+    benchmark.print();
+}
+
+fn count_odds_blocked_traversal(matrix: &Matrix<u8>) {
+    let mut benchmark = Benchmark::new("count_odds_blocked_traversal");
+    for _ in 0..ITERATIONS {
+        let (odds, duration) = time_it(|| count_odds_blocked(matrix, DIM, BLOCK));
+        benchmark.add(odds, duration);
+    }
+    benchmark.print();
+}
+
+fn count_odds_blocked_transpose_traversal(matrix: &Matrix<u8>) {
+    let mut benchmark = Benchmark::new("count_odds_blocked_transpose_traversal");
+    for _ in 0..ITERATIONS {
+        let (odds, duration) = time_it(|| count_odds_blocked_transpose(matrix, DIM, BLOCK));
+        benchmark.add(odds, duration);
     }
     benchmark.print();
 }
 
-fn count_odds_sequential(matrix: &[u8]) {
+fn count_odds_sequential(matrix: &Matrix<u8>) {
     let mut benchmark = Benchmark::new("count_odds_sequential");
     for _ in 0..ITERATIONS {
-        let t0 = Instant::now();
-        let mut odds = 0;
-        for i in 0..DIM {
-            for j in 0..DIM {
-                if matrix[i * DIM + j] % 2 != 0 {
-                    odds += 1;
-                }
-            }
-        }
-        benchmark.add(odds, t0.elapsed());
+        let (odds, duration) = time_it(|| count_odds_sequential_impl(matrix, DIM));
+        benchmark.add(odds, duration);
     }
     benchmark.print();
 }
 
-fn count_odds_parallel(matrix: &[u8]) {
-    let mut pool = Pool::new(P as u32);
+fn count_odds_parallel(matrix: &Matrix<u8>) {
     let mut benchmark = Benchmark::new("count_odds_parallel");
     for _ in 0..ITERATIONS {
-        let t0 = Instant::now();
-        let mut results = [0; P];
-        pool.scoped(|scope| {
-            for (p, results_p) in results.iter_mut().enumerate() {
-                scope.execute(move || {
-                    let chunk_size = DIM / P + 1;
-                    let my_start = p * chunk_size;
-                    let my_end = std::cmp::min(my_start + chunk_size, DIM);
-                    for i in my_start..my_end {
-                        for j in 0..DIM {
-                            if matrix[i * DIM + j] % 2 != 0 {
-                                *results_p += 1;
-                            }
-                        }
-                    }
-                });
-            }
-        });
-        let odds = results.iter().sum();
-        benchmark.add(odds, t0.elapsed());
+        let (odds, duration) = time_it(|| count_odds_parallel_impl(matrix, DIM));
+        benchmark.add(odds, duration);
     }
     benchmark.print();
 }
 
-fn count_odds_parallel_optimized(matrix: &[u8]) {
-    let mut pool = Pool::new(P as u32);
+fn count_odds_parallel_optimized_impl(matrix: &Matrix<u8>, dim: usize, p: usize) -> u32 {
+    let mut pool = Pool::new(p as u32);
+    let mut results = vec![0u32; p];
+    pool.scoped(|scope| {
+        for (w, results_w) in results.iter_mut().enumerate() {
+            scope.execute(move || {
+                let mut odds = 0;
+                let chunk_size = dim / p + 1;
+                let my_start = w * chunk_size;
+                let my_end = std::cmp::min(my_start + chunk_size, dim);
+                for i in my_start..my_end {
+                    for j in 0..dim {
+                        if !matrix[i][j].is_multiple_of(2) {
+                            odds += 1;
+                        }
+                    }
+                }
+                *results_w = odds;
+            });
+        }
+    });
+    results.iter().sum()
+}
+
+fn count_odds_parallel_optimized(matrix: &Matrix<u8>) {
     let mut benchmark = Benchmark::new("count_odds_parallel_optimized");
     for _ in 0..ITERATIONS {
-        let t0 = Instant::now();
-        let mut results = [0; P];
-        pool.scoped(|scope| {
-            for (p, results_p) in results.iter_mut().enumerate() {
-                scope.execute(move || {
-                    let mut odds = 0;
-                    let chunk_size = DIM / P + 1;
-                    let my_start = p * chunk_size;
-                    let my_end = std::cmp::min(my_start + chunk_size, DIM);
-                    for i in my_start..my_end {
-                        for j in 0..DIM {
-                            if matrix[i * DIM + j] % 2 != 0 {
-                                odds += 1;
-                            }
-                        }
+        let (odds, duration) = time_it(|| count_odds_parallel_optimized_impl(matrix, DIM, P));
+        benchmark.add(odds, duration);
+    }
+    benchmark.print();
+}
+
+fn run_sweep() {
+    let mut row_major = BenchmarkSweep::new("row_major");
+    let mut col_major = BenchmarkSweep::new("col_major");
+    let mut sequential = BenchmarkSweep::new("sequential");
+    let mut parallel = BenchmarkSweep::new("parallel");
+
+    for log2_n in (SWEEP_MIN_LOG2_N..=SWEEP_MAX_LOG2_N).step_by(2) {
+        let dim = 1usize << (log2_n / 2);
+        let n_elements = (dim * dim) as u64;
+        let matrix = build_random_u8_matrix(dim);
+
+        row_major.add(n_elements, sweep_throughput(n_elements, || count_odds_row_major(&matrix, dim)));
+        col_major.add(n_elements, sweep_throughput(n_elements, || count_odds_col_major(&matrix, dim)));
+        sequential.add(n_elements, sweep_throughput(n_elements, || count_odds_sequential_impl(&matrix, dim)));
+        parallel.add(n_elements, sweep_throughput(n_elements, || count_odds_parallel_impl(&matrix, dim)));
+    }
+
+    row_major.print();
+    col_major.print();
+    sequential.print();
+    parallel.print();
+}
+
+fn sweep_throughput<F: FnMut() -> u32>(n_elements: u64, f: F) -> f64 {
+    let mut benchmark = Benchmark::new("sweep");
+    let (odds, duration) = time_it(f);
+    benchmark.add(odds, duration);
+    benchmark.throughput(n_elements)
+}
+
+fn run_block_sweep() {
+    let matrix = build_random_u8_matrix(DIM);
+    let n_elements = (DIM * DIM) as u64;
+
+    let mut blocked = BenchmarkSweep::new("blocked_by_block");
+    let mut blocked_transpose = BenchmarkSweep::new("blocked_transpose_by_block");
+
+    for log2_block in BLOCK_SWEEP_MIN_LOG2..=BLOCK_SWEEP_MAX_LOG2 {
+        let block = 1usize << log2_block;
+        blocked.add(block as u64, sweep_throughput(n_elements, || count_odds_blocked(&matrix, DIM, block)));
+        blocked_transpose.add(
+            block as u64,
+            sweep_throughput(n_elements, || count_odds_blocked_transpose(&matrix, DIM, block)),
+        );
+    }
+
+    blocked.print();
+    blocked_transpose.print();
+}
+
+fn median_duration<F: FnMut() -> u32>(iterations: usize, mut f: F) -> Duration {
+    let mut durations: Vec<Duration> = (0..iterations).map(|_| time_it(&mut f).1).collect();
+    durations.sort();
+    durations[durations.len() / 2]
+}
+
+fn run_thread_sweep() {
+    let matrix = build_random_u8_matrix(DIM);
+    let max_p = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+
+    let baseline = median_duration(ITERATIONS, || count_odds_sequential_impl(&matrix, DIM));
+
+    println!("[thread_scaling]");
+    println!("{:>4}  {:>14}  {:>9}  {:>10}", "P", "median", "speedup", "efficiency");
+    let mut p = 1;
+    while p <= max_p {
+        let duration = median_duration(ITERATIONS, || count_odds_parallel_optimized_impl(&matrix, DIM, p));
+        let speedup = baseline.as_secs_f64() / duration.as_secs_f64();
+        let efficiency = speedup / p as f64;
+        println!("{:>4}  {:>14?}  {:>9.2}  {:>10.2}", p, duration, speedup, efficiency);
+        p *= 2;
+    }
+    println!();
+}
+
+fn build_random_u8_labels_matrix(dim: usize) -> Matrix<u8> {
+    Matrix::new((0..dim * dim).map(|_| rand::random::<u8>() % FLOOD_LABELS).collect(), dim)
+}
+
+fn build_random_u8_labels_nested(dim: usize) -> Vec<Vec<u8>> {
+    (0..dim)
+        .map(|_| (0..dim).map(|_| rand::random::<u8>() % FLOOD_LABELS).collect())
+        .collect()
+}
+
+fn count_components_matrix(grid: &Matrix<u8>, dim: usize, lifo: bool) -> u32 {
+    let mut marked = vec![false; dim * dim];
+    let mut components = 0;
+    let mut frontier: VecDeque<(usize, usize)> = VecDeque::new();
+
+    for r in 0..dim {
+        for c in 0..dim {
+            if marked[r * dim + c] {
+                continue;
+            }
+            components += 1;
+            marked[r * dim + c] = true;
+            frontier.push_back((r, c));
+            while let Some((cr, cc)) = if lifo { frontier.pop_back() } else { frontier.pop_front() } {
+                let label = grid[cr][cc];
+                for (dr, dc) in NEIGHBORS {
+                    let nr = cr as isize + dr;
+                    let nc = cc as isize + dc;
+                    if nr < 0 || nc < 0 || nr as usize >= dim || nc as usize >= dim {
+                        continue;
+                    }
+                    let (nr, nc) = (nr as usize, nc as usize);
+                    if !marked[nr * dim + nc] && grid[nr][nc] == label {
+                        marked[nr * dim + nc] = true;
+                        frontier.push_back((nr, nc));
+                    }
+                }
+            }
+        }
+    }
+    components
+}
+
+fn count_components_nested(grid: &[Vec<u8>], dim: usize, lifo: bool) -> u32 {
+    let mut marked = vec![false; dim * dim];
+    let mut components = 0;
+    let mut frontier: VecDeque<(usize, usize)> = VecDeque::new();
+
+    for r in 0..dim {
+        for c in 0..dim {
+            if marked[r * dim + c] {
+                continue;
+            }
+            components += 1;
+            marked[r * dim + c] = true;
+            frontier.push_back((r, c));
+            while let Some((cr, cc)) = if lifo { frontier.pop_back() } else { frontier.pop_front() } {
+                let label = grid[cr][cc];
+                for (dr, dc) in NEIGHBORS {
+                    let nr = cr as isize + dr;
+                    let nc = cc as isize + dc;
+                    if nr < 0 || nc < 0 || nr as usize >= dim || nc as usize >= dim {
+                        continue;
+                    }
+                    let (nr, nc) = (nr as usize, nc as usize);
+                    if !marked[nr * dim + nc] && grid[nr][nc] == label {
+                        marked[nr * dim + nc] = true;
+                        frontier.push_back((nr, nc));
                     }
-                    *results_p = odds;
-                });
+                }
             }
-        });
-        let odds = results.iter().sum();
-        benchmark.add(odds, t0.elapsed());
+        }
+    }
+    components
+}
+
+fn count_components_matrix_traversal(name: &'static str, grid: &Matrix<u8>, lifo: bool) {
+    let mut benchmark = Benchmark::new(name);
+    for _ in 0..ITERATIONS {
+        let (components, duration) = time_it(|| count_components_matrix(grid, FLOOD_DIM, lifo));
+        benchmark.add(components, duration);
     }
     benchmark.print();
 }
+
+fn count_components_nested_traversal(name: &'static str, grid: &[Vec<u8>], lifo: bool) {
+    let mut benchmark = Benchmark::new(name);
+    for _ in 0..ITERATIONS {
+        let (components, duration) = time_it(|| count_components_nested(grid, FLOOD_DIM, lifo));
+        benchmark.add(components, duration);
+    }
+    benchmark.print();
+}
+
+fn run_flood_fill_demo() {
+    let matrix = build_random_u8_labels_matrix(FLOOD_DIM);
+    count_components_matrix_traversal("count_components_matrix_stack", &matrix, true);
+    count_components_matrix_traversal("count_components_matrix_queue", &matrix, false);
+
+    let nested = build_random_u8_labels_nested(FLOOD_DIM);
+    count_components_nested_traversal("count_components_nested_stack", &nested, true);
+    count_components_nested_traversal("count_components_nested_queue", &nested, false);
+}
@@ -0,0 +1,49 @@
+use std::ops::{Index, IndexMut};
+
+pub struct Matrix<T> {
+    data: Vec<T>,
+    width: usize,
+}
+
+impl<T> Matrix<T> {
+    pub fn new(data: Vec<T>, width: usize) -> Matrix<T> {
+        assert!(width > 0 && data.len().is_multiple_of(width));
+        Matrix { data, width }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.data.len() / self.width
+    }
+
+    pub fn cols(&self) -> usize {
+        self.width
+    }
+}
+
+impl<T: Clone> Matrix<T> {
+    pub fn transpose(&self) -> Matrix<T> {
+        let rows = self.rows();
+        let cols = self.cols();
+        let mut data = Vec::with_capacity(self.data.len());
+        for c in 0..cols {
+            for r in 0..rows {
+                data.push(self[r][c].clone());
+            }
+        }
+        Matrix { data, width: rows }
+    }
+}
+
+impl<T> Index<usize> for Matrix<T> {
+    type Output = [T];
+
+    fn index(&self, row: usize) -> &[T] {
+        &self.data[row * self.width..][..self.width]
+    }
+}
+
+impl<T> IndexMut<usize> for Matrix<T> {
+    fn index_mut(&mut self, row: usize) -> &mut [T] {
+        &mut self.data[row * self.width..][..self.width]
+    }
+}
@@ -0,0 +1,69 @@
+struct Entry {
+    val: u64,
+    g: u64,
+    delta: u64,
+}
+
+pub struct Quantile {
+    epsilon: f64,
+    n: u64,
+    entries: Vec<Entry>,
+}
+
+impl Quantile {
+    pub fn new(epsilon: f64) -> Quantile {
+        Quantile {
+            epsilon,
+            n: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self, x: u64) {
+        let i = self.entries.partition_point(|e| e.val < x);
+        let is_boundary = i == 0 || i == self.entries.len();
+        let delta = if is_boundary {
+            0
+        } else {
+            (2.0 * self.epsilon * self.n as f64).floor() as u64
+        };
+        self.entries.insert(i, Entry { val: x, g: 1, delta });
+        self.n += 1;
+
+        let cap = (1.0 / (2.0 * self.epsilon)).ceil() as usize;
+        if self.entries.len() > cap {
+            self.compress();
+        }
+    }
+
+    fn compress(&mut self) {
+        let threshold = (2.0 * self.epsilon * self.n as f64).floor() as u64;
+        let mut i = 1;
+        while i + 1 < self.entries.len() {
+            let combined = self.entries[i].g + self.entries[i + 1].g + self.entries[i + 1].delta;
+            if combined <= threshold {
+                let removed = self.entries.remove(i);
+                self.entries[i].g += removed.g;
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    pub fn query(&self, phi: f64) -> u64 {
+        if self.entries.is_empty() {
+            return 0;
+        }
+        let target = phi * self.n as f64;
+        let band = self.epsilon * self.n as f64;
+        let mut rmin = 0u64;
+        for (i, entry) in self.entries.iter().enumerate() {
+            rmin += entry.g;
+            let rmax = rmin + entry.delta;
+            if rmax as f64 > target + band {
+                return self.entries[i.saturating_sub(1)].val;
+            }
+        }
+        self.entries.last().unwrap().val
+    }
+}